@@ -0,0 +1,180 @@
+use crate::runtime::*;
+
+// A compact instruction set for the visit/apply halves of a rewrite rule.
+// `Function::Compiled` needs a Rust compiler in the loop (see the
+// `[[CODEGEN:FAST-VISIT]]`/`[[CODEGEN:FAST-APPLY]]` markers in `reducer.rs`),
+// which means a rule set can't be shipped or hot-loaded without rebuilding
+// the runtime. `Bytecode` is the same logic as data: a small register/stack
+// VM interprets it directly inside the reducer, so rule sets can be
+// serialized, cached, and loaded without invoking rustc.
+#[derive(Clone, Debug)]
+pub enum Inst {
+  // Loads the term at argument `slot` of the current redex into `reg`.
+  LoadArg { reg: usize, slot: u64 },
+  // Dispatches on the tag of the Ptr held in `reg`, jumping to the
+  // instruction index paired with that tag (LAM/SUP/CTR/...) in `jump`;
+  // falls through to the next instruction if no arm matches.
+  MatchTag { reg: usize, jump: Vec<(Tag, usize)> },
+  // Allocates `n` consecutive locations and stores the base location in `reg`.
+  Alloc { reg: usize, n: u64 },
+  // Loads the current redex's host location into `reg` (mirrors `*ctx.host`).
+  LoadHost { reg: usize },
+  // Loads `term_reg`'s own base location, offset by `off` (mirrors
+  // `Ptr::loc(off)`), into `reg` — e.g. the location a SUP/LAM's own field
+  // lives at, as opposed to `TakeArg`, which reads the Ptr stored *there*.
+  TermLoc { reg: usize, term_reg: usize, off: u64 },
+  // Links `src` (a register holding a Ptr) to `dst_reg + dst_off` (a register
+  // holding a loc, e.g. one produced by `Alloc`, `TermLoc` or `LoadHost` —
+  // every real rewrite rule links into a location only known at
+  // interpretation time, never a fixed literal).
+  Link { dst_reg: usize, dst_off: u64, src: usize },
+  // Takes argument `slot` out of the term held by `term_reg`, storing the
+  // resulting Ptr into `reg` (mirrors `Heap::take_arg`).
+  TakeArg { reg: usize, term_reg: usize, slot: u64 },
+  // Substitutes `val_reg` for variable `var_reg` (mirrors `Heap::atomic_subst`).
+  Subst { var_reg: usize, val_reg: usize },
+  // Frees `n` locations starting at `loc_reg`.
+  Free { loc_reg: usize, n: u64 },
+  // Increments the global rewrite cost counter for this worker.
+  IncCost,
+  // Ends the rule, returning whether it rewrote anything.
+  Return { matched: bool },
+}
+
+// 16 general-purpose registers, each holding either a Ptr or a raw loc;
+// callers reinterpret via `ptr`/`loc` as the instruction demands.
+const NUM_REGS: usize = 16;
+
+#[derive(Clone, Copy)]
+enum Slot {
+  Empty,
+  Ptr(Ptr),
+  Loc(u64),
+}
+
+struct Regs([Slot; NUM_REGS]);
+
+impl Regs {
+  fn new() -> Self {
+    Self([Slot::Empty; NUM_REGS])
+  }
+
+  // `None` means `reg` was never written by an earlier instruction — a
+  // malformed or version-mismatched bytecode blob, not something a live
+  // Rust rule module could ever produce. `interpret` treats that as the
+  // rule simply not matching rather than unwrapping and panicking, since a
+  // bad blob loaded from outside the process shouldn't take the reducer
+  // thread down with it.
+  fn ptr(&self, reg: usize) -> Option<Ptr> {
+    match self.0[reg] {
+      Slot::Ptr(ptr) => Some(ptr),
+      Slot::Loc(loc) => Some(Var(loc)), // a bare location used where a Ptr is expected
+      Slot::Empty => None,
+    }
+  }
+
+  fn loc(&self, reg: usize) -> Option<u64> {
+    match self.0[reg] {
+      Slot::Loc(loc) => Some(loc),
+      Slot::Ptr(ptr) => Some(ptr.loc(0)),
+      Slot::Empty => None,
+    }
+  }
+}
+
+// Interprets `prog` (the visit or apply half of a `Function::Bytecode`)
+// against `ctx`, reusing the same heap primitives the hand-written rule
+// modules (`app`, `dup`, `op2`, ...) call directly.
+//
+// A bytecode blob can be loaded from outside the current build (that's the
+// whole point of compiling rules to data instead of Rust closures), so a
+// reference to a register nothing wrote yet — whether from a corrupt blob
+// or one compiled against a different instruction set — is treated as the
+// rule failing to match (`Return { matched: false }`'s own outcome) rather
+// than a reason to panic the reducer thread.
+pub fn interpret(ctx: &mut ReduceCtx, prog: &[Inst]) -> bool {
+  let mut regs = Regs::new();
+  let mut pc = 0;
+  while pc < prog.len() {
+    match &prog[pc] {
+      Inst::LoadArg { reg, slot } => {
+        regs.0[*reg] = Slot::Ptr(ctx.heap.load_arg(ctx.term, *slot));
+      }
+      Inst::MatchTag { reg, jump } => {
+        let Some(term) = regs.ptr(*reg) else { return false };
+        let tag = term.tag();
+        if let Some((_, target)) = jump.iter().find(|(t, _)| *t == tag) {
+          pc = *target;
+          continue;
+        }
+      }
+      Inst::Alloc { reg, n } => {
+        regs.0[*reg] = Slot::Loc(ctx.heap.alloc(ctx.tid, *n));
+      }
+      Inst::LoadHost { reg } => {
+        regs.0[*reg] = Slot::Loc(*ctx.host);
+      }
+      Inst::TermLoc { reg, term_reg, off } => {
+        let Some(term) = regs.ptr(*term_reg) else { return false };
+        regs.0[*reg] = Slot::Loc(term.loc(*off));
+      }
+      Inst::Link { dst_reg, dst_off, src } => {
+        let Some(dst) = regs.loc(*dst_reg) else { return false };
+        let Some(src) = regs.ptr(*src) else { return false };
+        ctx.heap.link(dst + dst_off, src);
+      }
+      Inst::TakeArg { reg, term_reg, slot } => {
+        let Some(term) = regs.ptr(*term_reg) else { return false };
+        regs.0[*reg] = Slot::Ptr(ctx.heap.take_arg(term, *slot));
+      }
+      Inst::Subst { var_reg, val_reg } => {
+        let Some(var) = regs.loc(*var_reg) else { return false };
+        let Some(val) = regs.ptr(*val_reg) else { return false };
+        ctx.heap.atomic_subst(&ctx.prog.aris, ctx.tid, Var(var), val);
+      }
+      Inst::Free { loc_reg, n } => {
+        let Some(loc) = regs.loc(*loc_reg) else { return false };
+        ctx.heap.free(ctx.tid, loc, *n);
+      }
+      Inst::IncCost => {
+        ctx.heap.inc_cost(ctx.tid);
+      }
+      Inst::Return { matched } => {
+        return *matched;
+      }
+    }
+    pc += 1;
+  }
+  false
+}
+
+// NOTE: `interpret` itself isn't tested here — exercising it needs a real
+// `ReduceCtx`, which means a constructible `Heap`/`Program`, neither of
+// which live in this file or anywhere else in this tree. `Regs` is the
+// self-contained part of this module, so that's what's covered below.
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn empty_register_reads_as_none() {
+    let regs = Regs::new();
+    assert!(regs.ptr(0).is_none());
+    assert!(regs.loc(0).is_none());
+  }
+
+  #[test]
+  fn loc_register_reads_back_as_a_var_ptr() {
+    let mut regs = Regs::new();
+    regs.0[0] = Slot::Loc(7);
+    assert_eq!(regs.loc(0), Some(7));
+    assert_eq!(regs.ptr(0).unwrap().loc(0), 7); // Var(7)'s own loc(0) is 7
+  }
+
+  #[test]
+  fn ptr_register_reads_back_its_loc() {
+    let mut regs = Regs::new();
+    regs.0[0] = Slot::Ptr(Var(9));
+    assert_eq!(regs.loc(0), Some(9));
+  }
+}
@@ -15,6 +15,18 @@ pub struct ReduceCtx<'a> {
   pub host: &'a mut u64,
 }
 
+// Lazy mode trades eager "expand everything reachable from WHNF" for
+// "expand only each node's principal port". With `lazy: true`, the `full`
+// expansion below only ever follows one child per node (the function side
+// of an APP, the first branch of a SUP, the first arg of a CTR/FUN) instead
+// of fanning out to every child. In both modes a DP0/DP1's shared value is
+// pushed at most once across both projections (memoized via `forced`)
+// rather than walked twice. A subterm nothing ever demands this way is never forced,
+// even if it would loop or diverge. This is what lets HVM evaluate
+// coinductive/streaming programs (e.g. an infinite stream whose caller only
+// pulls a finite prefix) that the strict walk below would hang on trying to
+// normalize in full.
+
 // HVM's reducer is a finite stack machine with 4 possible states:
 // - visit: visits a node and add its children to the visit stack ~> visit, apply, blink
 // - apply: reduces a node, applying a rewrite rule               ~> visit, apply, blink, halt
@@ -36,25 +48,66 @@ pub fn is_whnf(term: Ptr) -> bool {
   term.tag().is_whnf()
 }
 
+// Outcome of a (possibly budgeted) reduction: either the root reached the
+// state `reduce`/`normalize` were asked for, or the rewrite budget ran out
+// first. In the `Budget` case the heap's visit queues and redex bag are left
+// exactly as the workers dropped them, so resuming is just calling
+// `reduce_budgeted` again with the same `root`.
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub enum ReduceStatus {
+  Whnf,
+  Budget,
+}
+
 impl Heap {
   pub fn reduce(&self, prog: &Program, tids: &[usize], root: u64, full: bool, debug: bool) -> Ptr {
+    self.reduce_mode(prog, tids, root, full, false, debug)
+  }
+
+  // Same as `reduce`, but with `lazy: true` the reducer only follows each
+  // WHNF's principal port instead of pushing every child onto the visit
+  // queue. Has no effect
+  // unless `full` is also set, since that's the only place this expansion
+  // happens.
+  pub fn reduce_mode(&self, prog: &Program, tids: &[usize], root: u64, full: bool, lazy: bool, debug: bool) -> Ptr {
+    self.reduce_budgeted(prog, tids, root, full, lazy, None, debug).0
+  }
+
+  // Fuel-metered reduction: each worker checks the global rewrite cost
+  // against `max_rewrites` after every successful apply, and once it's
+  // exceeded, flips `stop` so all workers drain without discarding their
+  // visit stacks or the shared redex bag. This makes HVM usable as an
+  // embedded engine that must stay responsive or cap work done on behalf of
+  // untrusted programs: callers get back a status instead of blocking
+  // forever, and can resume by calling this again with the same `root`.
+  pub fn reduce_budgeted(
+    &self,
+    prog: &Program,
+    tids: &[usize],
+    root: u64,
+    full: bool,
+    lazy: bool,
+    max_rewrites: Option<u64>,
+    debug: bool,
+  ) -> (Ptr, ReduceStatus) {
     // Halting flag
     let stop = &AtomicUsize::new(1);
     let barr = &Barrier::new(tids.len());
     let locs = &tids.iter().map(|x| AtomicU64::new(u64::MAX)).collect::<Vec<AtomicU64>>();
+    let over_budget = &AtomicBool::new(false);
 
     // Spawn a thread for each worker
     std::thread::scope(|s| {
       for tid in tids {
         s.spawn(move || {
-          reducer(self, prog, tids, stop, barr, locs, root, *tid, full, debug);
+          reducer(self, prog, tids, stop, barr, locs, over_budget, max_rewrites, root, *tid, full, lazy, debug);
           //println!("[{}] done", tid);
         });
       }
     });
 
-    // Return whnf term ptr
-    self.load_ptr(root)
+    let status = if over_budget.load(Ordering::Relaxed) { ReduceStatus::Budget } else { ReduceStatus::Whnf };
+    (self.load_ptr(root), status)
   }
 }
 
@@ -65,9 +118,12 @@ fn reducer(
   stop: &AtomicUsize,
   barr: &Barrier,
   locs: &[AtomicU64],
+  over_budget: &AtomicBool,
+  max_rewrites: Option<u64>,
   root: u64,
   tid: usize,
   full: bool,
+  lazy: bool,
   debug: bool,
 ) {
   // State Stacks
@@ -76,6 +132,8 @@ fn reducer(
   let bkoff = &Backoff::new();
   let hold = tids.len() <= 1;
   let seen = &mut HashSet::new();
+  let mut steal_round: u64 = 0;
+  let forced = &mut HashSet::new();
 
   // State Vars
   let (mut cont, mut host) = if tid == tids[0] { (REDEX_CONT_RET, root) } else { (0, u64::MAX) };
@@ -98,6 +156,26 @@ fn reducer(
         break 'init;
       }
       'work: loop {
+        // Fuel check: runs once per trip through `'work`, i.e. right after
+        // whatever apply rule fired last, before this thread does any more
+        // visiting or rewriting.
+        if let Some(budget) = max_rewrites {
+          if heap.get_cost() >= budget {
+            // `host` is this thread's in-flight task: whatever it was about
+            // to visit or re-enter next. Push it back onto this tid's own
+            // `VisitQueue` before abandoning ship, or it's simply lost —
+            // harmless for the lead thread, which always restarts its walk
+            // from `root` on the next `reduce_budgeted` call, but fatal for
+            // every other thread, whose `(cont, host)` resets to its
+            // default on resume and has no other way to recover a task
+            // discovered via stealing. Without this, resuming can settle
+            // into `Whnf` having silently never forced part of the graph.
+            visit.push(new_visit(host, hold, cont));
+            over_budget.store(true, Ordering::Relaxed);
+            stop.store(0, Ordering::Relaxed);
+            break 'main;
+          }
+        }
         'visit: loop {
           let term = heap.load_ptr(host);
           if debug {
@@ -207,6 +285,26 @@ fn reducer(
                     break 'visit;
                   }
                 }
+                Some(Function::Bytecode { smap: fn_smap, visit: fn_visit, apply: fn_apply }) => {
+                  if bytecode::interpret(
+                    &mut ReduceCtx {
+                      heap,
+                      prog,
+                      tid,
+                      hold,
+                      term,
+                      visit,
+                      redex,
+                      cont: &mut cont,
+                      host: &mut host,
+                    },
+                    fn_visit,
+                  ) {
+                    continue 'visit;
+                  } else {
+                    break 'visit;
+                  }
+                }
                 None => {
                   break 'visit;
                 }
@@ -325,6 +423,26 @@ fn reducer(
                       break 'apply;
                     }
                   }
+                  Some(Function::Bytecode { smap: fn_smap, visit: fn_visit, apply: fn_apply }) => {
+                    if bytecode::interpret(
+                      &mut ReduceCtx {
+                        heap,
+                        prog,
+                        tid,
+                        hold,
+                        term,
+                        visit,
+                        redex,
+                        cont: &mut cont,
+                        host: &mut host,
+                      },
+                      fn_apply,
+                    ) {
+                      continue 'work;
+                    } else {
+                      break 'apply;
+                    }
+                  }
                   None => {
                     break 'apply;
                   }
@@ -339,6 +457,22 @@ fn reducer(
           if cont == REDEX_CONT_RET {
             //println!("done {}", show_at(heap, prog, host, &[]));
             stop.fetch_sub(1, Ordering::Relaxed);
+            // Eager mode fans out to every child so the whole term reaches
+            // normal form. Lazy mode only ever follows a node's principal
+            // port — the one child an enclosing interaction would actually
+            // demand next (the function side of an APP, the first branch of
+            // a SUP, the first arg of a CTR/FUN) — so a subterm nothing ever
+            // observes is never forced, even if it would loop or diverge.
+            //
+            // DP0/DP1 are a special case rather than just "skip it": both
+            // projections of the same dup share the same `term.loc(2)`, so
+            // without memoization, forcing one projection and then the
+            // other would walk and re-normalize that shared value twice.
+            // `forced` is that memo: the first projection to reach this
+            // point marks the shared value pushed so the second one's
+            // fan-out becomes a no-op instead of a redundant indirection.
+            // It is kept apart from `seen`, so the shared value's own visit
+            // still fans out to its children once it reaches WHNF.
             if full && !seen.contains(&host) {
               seen.insert(host);
               let term = heap.load_ptr(host);
@@ -348,28 +482,39 @@ fn reducer(
                   visit.push(new_visit(term.loc(1), hold, cont));
                 }
                 Tag::APP => {
-                  stop.fetch_add(2, Ordering::Relaxed);
-                  visit.push(new_visit(term.loc(0), hold, cont));
-                  visit.push(new_visit(term.loc(1), hold, cont));
+                  if lazy {
+                    stop.fetch_add(1, Ordering::Relaxed);
+                    visit.push(new_visit(term.loc(0), hold, cont));
+                  } else {
+                    stop.fetch_add(2, Ordering::Relaxed);
+                    visit.push(new_visit(term.loc(0), hold, cont));
+                    visit.push(new_visit(term.loc(1), hold, cont));
+                  }
                 }
                 Tag::SUP => {
-                  stop.fetch_add(2, Ordering::Relaxed);
-                  visit.push(new_visit(term.loc(0), hold, cont));
-                  visit.push(new_visit(term.loc(1), hold, cont));
-                }
-                Tag::DP0 => {
-                  stop.fetch_add(1, Ordering::Relaxed);
-                  visit.push(new_visit(term.loc(2), hold, cont));
+                  if lazy {
+                    stop.fetch_add(1, Ordering::Relaxed);
+                    visit.push(new_visit(term.loc(0), hold, cont));
+                  } else {
+                    stop.fetch_add(2, Ordering::Relaxed);
+                    visit.push(new_visit(term.loc(0), hold, cont));
+                    visit.push(new_visit(term.loc(1), hold, cont));
+                  }
                 }
-                Tag::DP1 => {
-                  stop.fetch_add(1, Ordering::Relaxed);
-                  visit.push(new_visit(term.loc(2), hold, cont));
+                Tag::DP0 | Tag::DP1 => {
+                  let val = term.loc(2);
+                  if !forced.contains(&val) {
+                    forced.insert(val);
+                    stop.fetch_add(1, Ordering::Relaxed);
+                    visit.push(new_visit(val, hold, cont));
+                  }
                 }
                 Tag::CTR | Tag::FUN => {
                   let arit = prog.aris.arity_of(term);
                   if arit > 0 {
-                    stop.fetch_add(arit as usize, Ordering::Relaxed);
-                    for i in 0..arit {
+                    let n = if lazy { 1 } else { arit as usize };
+                    stop.fetch_add(n, Ordering::Relaxed);
+                    for i in 0..n as u64 {
                       visit.push(new_visit(term.loc(i), hold, cont));
                     }
                   }
@@ -412,9 +557,14 @@ fn reducer(
         //println!("[{}] stop", tid);
         break 'main;
       } else {
-        for victim_tid in tids {
-          if *victim_tid != tid {
-            if let Some((new_cont, new_host)) = heap.vstk[*victim_tid].steal() {
+        // Steal from a randomized victim order each round, instead of
+        // always scanning `tids` from the front, so stealers spread out
+        // across victims rather than piling onto whichever queue sits
+        // first in the slice.
+        steal_round += 1;
+        for victim_tid in shuffled_tids(tids, (tid as u64).wrapping_mul(0x9E3779B97F4A7C15) ^ steal_round) {
+          if victim_tid != tid {
+            if let Some((new_cont, new_host)) = heap.vstk[victim_tid].steal() {
               cont = new_cont;
               host = new_host;
               //println!("stolen");
@@ -431,9 +581,16 @@ fn reducer(
 
 impl Heap {
   pub fn normalize(&self, prog: &Program, tids: &[usize], host: u64, debug: bool) -> Ptr {
+    self.normalize_mode(prog, tids, host, false, debug)
+  }
+
+  // Same as `normalize`, but with `lazy: true` only reduces what's demanded
+  // along the spine, instead of eagerly visiting every child of every WHNF.
+  // Useful for programs whose normal form would otherwise be infinite.
+  pub fn normalize_mode(&self, prog: &Program, tids: &[usize], host: u64, lazy: bool, debug: bool) -> Ptr {
     let mut cost = self.get_cost();
     loop {
-      self.reduce(prog, tids, host, true, debug);
+      self.reduce_mode(prog, tids, host, true, lazy, debug);
       let new_cost = self.get_cost();
       if new_cost != cost {
         cost = new_cost;
@@ -443,6 +600,34 @@ impl Heap {
     }
     self.load_ptr(host)
   }
+
+  // Fuel-metered normalization: loops like `normalize_mode` until cost
+  // stabilizes, but returns `Budget` as soon as the rewrite budget is
+  // exhausted, even mid-spine.
+  // Resuming is calling this again with the same `host`.
+  pub fn normalize_budgeted(
+    &self,
+    prog: &Program,
+    tids: &[usize],
+    host: u64,
+    lazy: bool,
+    max_rewrites: u64,
+    debug: bool,
+  ) -> ReduceStatus {
+    let mut cost = self.get_cost();
+    loop {
+      let (_, status) = self.reduce_budgeted(prog, tids, host, true, lazy, Some(max_rewrites), debug);
+      if status == ReduceStatus::Budget {
+        return ReduceStatus::Budget;
+      }
+      let new_cost = self.get_cost();
+      if new_cost != cost {
+        cost = new_cost;
+      } else {
+        return ReduceStatus::Whnf;
+      }
+    }
+  }
 }
 //pub fn normal(heap: &Heap, prog: &Program, tids: &[usize], host: u64, seen: &mut im::HashSet<u64>, debug: bool) -> Ptr {
 //let term = heap.load_ptr( host);
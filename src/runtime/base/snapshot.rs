@@ -0,0 +1,308 @@
+use crate::runtime::*;
+use std::collections::HashMap;
+use std::fmt::Write as _;
+
+// Textual interaction-net snapshot: freezes the *whole* live graph reachable
+// from a host (not just the WHNF `reduce` would return), including whatever
+// redexes are still pending in `rbag`, so a long-running reduction can be
+// checkpointed and later reloaded to continue exactly where it left off.
+//
+// Format, one definition per line:
+//   $<n> = <node>
+// where `<node>` is one of:
+//   Lam $varslot $body
+//   App $func $argm
+//   Sup <lab> $fst $snd
+//   Dp0 <lab> $val   |   Dp1 <lab> $val
+//   Ctr <fid> $a0 $a1 ...
+//   Fun <fid> $a0 $a1 ...
+//   Op2 <opx> $fst $snd
+//   Var $slot
+//   Num <val>
+// Any other tag (e.g. ERA) is written as `Ptr <tag> <loc>`; `load_net` does
+// not read that form and panics on it.
+// and `$n` is a wire name: either the name assigned the first time a loc is
+// visited, or a back-reference to it, so shared (`dup`-bound) and cyclic
+// (`Var`-bound) wiring round-trips exactly instead of being unfolded into a
+// tree. `Var $slot` names the binder's var-slot cell the variable points at
+// (often itself, `$k = Var $k`, for a not-yet-substituted var), not a
+// separate child. `Lam`'s own `$varslot` is that same cell (`Lam`'s
+// `loc(0)`), dumped explicitly rather than left to be discovered through a
+// `Var` occurrence, so a lambda whose bound variable is never referenced in
+// its body still round-trips the binder's address.
+//
+// A pending redex is dumped as its own line instead of a `$n = ...` def:
+//   redex[<key> <saved_cont>] = $host
+// `key` and `saved_cont` are opaque `RedexBag` ids, not wires.
+impl Heap {
+  pub fn dump_net(&self, prog: &Program, root: u64) -> String {
+    let mut out = String::new();
+    let mut named: HashMap<u64, u64> = HashMap::new();
+    let mut fresh: u64 = 0;
+
+    let root_wire = self.dump_node(prog, root, &mut named, &mut fresh, &mut out);
+    let _ = writeln!(out, "root = {}", root_wire);
+
+    // Any redex still awaiting its continuation also has to survive the
+    // round-trip, or resuming would silently drop in-flight work. Each
+    // pending entry is filed under a `key` that a child presents to
+    // `RedexBag::complete` to get back `(saved_cont, saved_host)`, so all
+    // three fields have to be dumped: `key` and `saved_cont` are opaque ids
+    // (not heap locations, dumped as-is), `saved_host` is a wire like any
+    // other term reference.
+    for (key, saved_cont, saved_host) in self.rbag.pending() {
+      let wire = self.dump_node(prog, saved_host, &mut named, &mut fresh, &mut out);
+      let _ = writeln!(out, "redex[{} {}] = {}", key, saved_cont, wire);
+    }
+
+    out
+  }
+
+  fn dump_node(
+    &self,
+    prog: &Program,
+    loc: u64,
+    named: &mut HashMap<u64, u64>,
+    fresh: &mut u64,
+    out: &mut String,
+  ) -> String {
+    if let Some(id) = named.get(&loc) {
+      return format!("${}", id);
+    }
+    let id = *fresh;
+    *fresh += 1;
+    named.insert(loc, id);
+    let wire = format!("${}", id);
+
+    let term = self.load_ptr(loc);
+    let body = match term.tag() {
+      // A `Var`'s own `loc(0)` is the binder's var-slot cell, not this
+      // cell — dump it the same way any other child reference is dumped so
+      // an unbound var (which points back at its own slot) round-trips as
+      // a back-reference (`$k = Var $k`) instead of losing its binding.
+      Tag::VAR => {
+        let target = self.dump_node(prog, term.loc(0), named, fresh, out);
+        format!("Var {}", target)
+      }
+      Tag::LAM => {
+        // The var slot has to be dumped as its own wire, not just picked up
+        // incidentally when a `Var` occurrence in the body happens to
+        // reference it — an unused bound variable has no such occurrence,
+        // and `load_net` needs `$varslot`'s id to know which wire to tie
+        // back to this `Lam`'s own `loc(0)` on reload.
+        let var = self.dump_node(prog, term.loc(0), named, fresh, out);
+        let body = self.dump_node(prog, term.loc(1), named, fresh, out);
+        format!("Lam {} {}", var, body)
+      }
+      Tag::APP => {
+        let func = self.dump_node(prog, term.loc(0), named, fresh, out);
+        let argm = self.dump_node(prog, term.loc(1), named, fresh, out);
+        format!("App {} {}", func, argm)
+      }
+      Tag::SUP => {
+        let fst = self.dump_node(prog, term.loc(0), named, fresh, out);
+        let snd = self.dump_node(prog, term.loc(1), named, fresh, out);
+        format!("Sup {} {} {}", term.ext(), fst, snd)
+      }
+      Tag::DP0 => {
+        let val = self.dump_node(prog, term.loc(2), named, fresh, out);
+        format!("Dp0 {} {}", term.ext(), val)
+      }
+      Tag::DP1 => {
+        let val = self.dump_node(prog, term.loc(2), named, fresh, out);
+        format!("Dp1 {} {}", term.ext(), val)
+      }
+      Tag::OP2 => {
+        let fst = self.dump_node(prog, term.loc(0), named, fresh, out);
+        let snd = self.dump_node(prog, term.loc(1), named, fresh, out);
+        format!("Op2 {} {} {}", term.ext(), fst, snd)
+      }
+      Tag::CTR | Tag::FUN => {
+        let arity = prog.aris.arity_of(term);
+        let mut args = Vec::with_capacity(arity as usize);
+        for i in 0..arity {
+          args.push(self.dump_node(prog, term.loc(i), named, fresh, out));
+        }
+        let kind = if term.tag() == Tag::CTR { "Ctr" } else { "Fun" };
+        format!("{} {} {}", kind, term.ext(), args.join(" "))
+      }
+      Tag::NUM => format!("Num {}", term.loc(0)),
+      _ => format!("Ptr {} {}", term.tag() as u64, term.loc(0)),
+    };
+
+    let _ = writeln!(out, "${} = {}", id, body);
+    wire
+  }
+
+  // Reconstructs a heap allocation from text emitted by `dump_net`, returning
+  // the host of the original `root` wire. Wires are resolved in two passes:
+  // first every `$n = ...` line gets a fresh loc allocated (so a forward
+  // reference to a not-yet-defined wire — including a `Var` referencing its
+  // own binder's slot before that binder's own def line has been built, since
+  // `dump_node` always writes a node's line after its children — can still
+  // be linked), then a second pass fills in the node contents now that every
+  // wire has a loc. `Lam`'s `$varslot` is the one exception to "every wire
+  // gets its own fresh loc": a `Lam`'s `loc(0)` *is* that var slot, so its
+  // wire has to resolve to the same 2-cell block the `Lam` itself allocates,
+  // not an independent cell, or the bound variable ends up pointing at an
+  // orphan. The location pass allocates that block up front and seeds
+  // `wire_loc` with it before handing out fresh cells to everything else.
+  // Pending redexes are re-registered last, once every wire they reference
+  // has a loc to resolve to a host.
+  pub fn load_net(&self, prog: &Program, tid: usize, text: &str) -> u64 {
+    let mut wire_loc: HashMap<u64, u64> = HashMap::new();
+    let mut defs: Vec<(u64, Vec<&str>)> = Vec::new();
+    let mut redexes: Vec<(u64, u64, &str)> = Vec::new();
+    let mut root_wire: Option<u64> = None;
+
+    for line in text.lines() {
+      let line = line.trim();
+      if line.is_empty() {
+        continue;
+      }
+      let (lhs, rhs) = line.split_once('=').expect("snapshot: malformed line, expected `lhs = rhs`");
+      let lhs = lhs.trim();
+      let rhs: Vec<&str> = rhs.trim().split_whitespace().collect();
+      if lhs == "root" {
+        root_wire = Some(parse_wire(rhs[0]));
+        continue;
+      }
+      if let Some(head) = lhs.strip_prefix("redex[").and_then(|s| s.strip_suffix(']')) {
+        let mut head = head.split_whitespace();
+        let key: u64 = head.next().expect("snapshot: malformed redex key").parse().expect("snapshot: bad redex key");
+        let saved_cont: u64 =
+          head.next().expect("snapshot: malformed redex saved cont").parse().expect("snapshot: bad redex saved cont");
+        redexes.push((key, saved_cont, rhs[0]));
+        continue;
+      }
+      let id = parse_wire(lhs);
+      defs.push((id, rhs));
+    }
+
+    // A `Lam` def's `$varslot` (rhs[1]) resolves to the `Lam`'s own 2-cell
+    // block, not a standalone cell, so it has to claim that block here,
+    // before the generic pass below hands out fresh single cells to every
+    // wire that hasn't already got a loc.
+    for (_, rhs) in &defs {
+      if rhs[0] == "Lam" {
+        let var_id = parse_wire(rhs[1]);
+        wire_loc.entry(var_id).or_insert_with(|| self.alloc(tid, 2));
+      }
+    }
+
+    for (id, _) in &defs {
+      wire_loc.entry(*id).or_insert_with(|| self.alloc(tid, 1));
+    }
+
+    for (id, rhs) in &defs {
+      let loc = wire_loc[id];
+      let ptr = self.build_node(prog, tid, rhs, &wire_loc);
+      self.link(loc, ptr);
+    }
+
+    // Re-register every pending redex under its original key so the reducer
+    // can still find it via `RedexBag::complete` after reload, instead of
+    // the in-flight work it guards silently vanishing.
+    for (key, saved_cont, wire) in redexes {
+      let saved_host = wire_loc[&parse_wire(wire)];
+      self.rbag.insert_at(tid, key, new_redex(saved_host, saved_cont, 1));
+    }
+
+    wire_loc[&root_wire.expect("snapshot: missing `root = ...` line")]
+  }
+
+  fn build_node(&self, prog: &Program, tid: usize, rhs: &[&str], wire_loc: &HashMap<u64, u64>) -> Ptr {
+    let wire = |tok: &str| self.load_ptr(wire_loc[&parse_wire(tok)]);
+    match rhs[0] {
+      // Points at the wire's own cell (its binder's var-slot), not its
+      // content, so this doesn't care whether that wire's def line has been
+      // built yet — every wire's loc is already reserved by the allocation
+      // pass above. A self-reference (`Var $k` inside `$k`'s own def) just
+      // means the variable is still unbound.
+      "Var" => Var(wire_loc[&parse_wire(rhs[1])]),
+      "Lam" => {
+        // `loc` was already allocated by `load_net`'s location pass and
+        // seeded as `$varslot`'s wire, so the var slot (loc+0) round-trips
+        // to the same cell the bound variable's `Var` occurrences point at,
+        // instead of an independently-allocated cell nothing reads.
+        let loc = wire_loc[&parse_wire(rhs[1])];
+        self.link(loc + 1, wire(rhs[2]));
+        Lam(loc)
+      }
+      "App" => {
+        let loc = self.alloc(tid, 2);
+        self.link(loc + 0, wire(rhs[1]));
+        self.link(loc + 1, wire(rhs[2]));
+        App(loc)
+      }
+      "Sup" => {
+        let lab: u64 = rhs[1].parse().expect("snapshot: bad Sup label");
+        let loc = self.alloc(tid, 2);
+        self.link(loc + 0, wire(rhs[2]));
+        self.link(loc + 1, wire(rhs[3]));
+        Sup(lab, loc)
+      }
+      "Dp0" | "Dp1" => {
+        let lab: u64 = rhs[1].parse().expect("snapshot: bad Dup label");
+        let loc = self.alloc(tid, 3);
+        self.link(loc + 2, wire(rhs[2]));
+        if rhs[0] == "Dp0" { Dp0(lab, loc) } else { Dp1(lab, loc) }
+      }
+      "Op2" => {
+        let opx: u64 = rhs[1].parse().expect("snapshot: bad Op2 opcode");
+        let loc = self.alloc(tid, 2);
+        self.link(loc + 0, wire(rhs[2]));
+        self.link(loc + 1, wire(rhs[3]));
+        Op2(opx, loc)
+      }
+      "Ctr" | "Fun" => {
+        let fid: u64 = rhs[1].parse().expect("snapshot: bad Ctr/Fun id");
+        let args = &rhs[2..];
+        let loc = self.alloc(tid, args.len() as u64);
+        for (i, arg) in args.iter().enumerate() {
+          self.link(loc + i as u64, wire(arg));
+        }
+        if rhs[0] == "Ctr" { Ctr(fid, loc) } else { Fun(fid, loc) }
+      }
+      "Num" => Num(rhs[1].parse().expect("snapshot: bad Num value")),
+      other => panic!("snapshot: unknown node kind `{}`", other),
+    }
+  }
+}
+
+fn parse_wire(tok: &str) -> u64 {
+  tok.strip_prefix('$').unwrap_or(tok).parse().expect("snapshot: expected a `$n` wire reference")
+}
+
+// NOTE: a golden `dump_net`/`load_net` round-trip test (build a graph,
+// dump it, reload it, assert it reduces identically) belongs here, and is
+// exactly the kind of case that would have caught both the pending-redex
+// bug fixed in 6e349be and the `Lam` var-slot bug fixed in a03c4da before
+// they needed a follow-up commit. It's not added in this commit because
+// building the fixture graph needs `Heap::new`/`Program::new` (and enough
+// of their alloc/arity API to hand-construct a small `Lam`/`App`/`Dp0`-`Dp1`
+// net), none of which live in this file or anywhere else in this tree —
+// see `parse_wire`'s own tests below for the part of this module that
+// *is* self-contained enough to test without them.
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn parse_wire_accepts_dollar_prefix() {
+    assert_eq!(parse_wire("$42"), 42);
+  }
+
+  #[test]
+  fn parse_wire_accepts_bare_digits() {
+    // The `$` is stripped if present, not required, so a wire token that
+    // somehow lost its prefix still parses instead of panicking.
+    assert_eq!(parse_wire("42"), 42);
+  }
+
+  #[test]
+  #[should_panic(expected = "expected a `$n` wire reference")]
+  fn parse_wire_rejects_garbage() {
+    parse_wire("$nope");
+  }
+}
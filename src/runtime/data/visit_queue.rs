@@ -0,0 +1,153 @@
+use crossbeam_deque::{Steal, Stealer, Worker};
+
+// Per-worker visit queue, backed by a Chase-Lev work-stealing deque
+// (crossbeam-deque). The owning thread pushes/pops from the bottom of its
+// own `Worker` (LIFO); every other thread only ever sees a `Stealer` handle
+// and steals from the top (FIFO, a single CAS on `top`, fully lock-free).
+// This replaces the old hand-rolled structure and its linear victim scan,
+// which didn't scale past a handful of cores.
+//
+// `Worker<T>` is `Send` but deliberately not `Sync`: its push/pop mutate a
+// `bottom` index and a `Cell`-backed buffer pointer with no cross-thread
+// synchronization beyond a release store, which is only sound with a single
+// writer. A `VisitQueue` lives in `Heap::vstk` and is reached through a
+// shared `&Heap` by every thread in the `std::thread::scope` — including
+// threads that are not its owner, so the field has to be `Sync` — but
+// `reducer()` only ever calls push/pop on `heap.vstk[tid]` from the thread
+// running as `tid` itself (and visit-queue state is expected to survive
+// budgeted pause/resume, so the `Worker` has to live here rather than on
+// the reducer's stack). Every other thread only reaches this slot through
+// the already-`Send + Sync` `Stealer` half. `OwnedWorker` asserts that
+// single-owner invariant directly instead of reaching for a `Mutex`, which
+// would make the struct `Sync` too but put a lock on every single push/pop
+// — i.e. every visit and blink — defeating the point of a lock-free
+// Chase-Lev deque.
+struct OwnedWorker<T>(Worker<T>);
+
+// SAFETY: only the owning tid's reducer thread ever calls through to the
+// wrapped `Worker` (see above); every other thread only touches `Stealer`,
+// which is genuinely `Sync` on its own.
+unsafe impl<T> Sync for OwnedWorker<T> {}
+
+pub struct VisitQueue {
+  worker: OwnedWorker<(u64, u64)>,
+  stealer: Stealer<(u64, u64)>,
+}
+
+impl VisitQueue {
+  pub fn new() -> Self {
+    let worker = Worker::new_lifo();
+    let stealer = worker.stealer();
+    Self { worker: OwnedWorker(worker), stealer }
+  }
+
+  // Pushed/popped only by the owning worker thread; see `OwnedWorker` above
+  // for why that's sound without a lock.
+  #[inline(always)]
+  pub fn push(&self, task: (u64, u64)) {
+    self.worker.0.push(task);
+  }
+
+  #[inline(always)]
+  pub fn pop(&self) -> Option<(u64, u64)> {
+    self.worker.0.pop()
+  }
+
+  // Called by other threads to steal from this queue's top. Retries on
+  // `Steal::Retry`, since that just means we raced a concurrent pop/steal
+  // for the last element and lost; the deque itself resolves that race with
+  // a CAS on `top`, so spinning here is cheap and bounded.
+  #[inline(always)]
+  pub fn steal(&self) -> Option<(u64, u64)> {
+    loop {
+      match self.stealer.steal() {
+        Steal::Success(task) => return Some(task),
+        Steal::Empty => return None,
+        Steal::Retry => continue,
+      }
+    }
+  }
+}
+
+impl Default for VisitQueue {
+  fn default() -> Self {
+    Self::new()
+  }
+}
+
+// Cheap, non-cryptographic xorshift used only to pick a random steal order
+// per round. Avoids pulling in a `rand` dependency for what is just "don't
+// always scan victims in tid order".
+#[inline(always)]
+fn xorshift(mut x: u64) -> u64 {
+  x ^= x << 13;
+  x ^= x >> 7;
+  x ^= x << 17;
+  x
+}
+
+// Returns the tids in a pseudo-random order seeded by `seed` (e.g. a mix of
+// the stealing thread's tid and a per-attempt counter), so concurrent
+// stealers spread their load across victims instead of hammering the same
+// one first.
+pub fn shuffled_tids(tids: &[usize], seed: u64) -> Vec<usize> {
+  let mut out = tids.to_vec();
+  let mut state = xorshift(seed | 1);
+  for i in (1..out.len()).rev() {
+    state = xorshift(state);
+    let j = (state as usize) % (i + 1);
+    out.swap(i, j);
+  }
+  out
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn xorshift_never_collapses_to_zero() {
+    // A zero state would stick forever (xorshift(0) == 0), so `seed | 1`
+    // in `shuffled_tids` has to keep the stream away from it.
+    let mut x = 1;
+    for _ in 0..1000 {
+      x = xorshift(x);
+      assert_ne!(x, 0);
+    }
+  }
+
+  #[test]
+  fn shuffled_tids_is_a_permutation() {
+    let tids: Vec<usize> = (0..8).collect();
+    let mut out = shuffled_tids(&tids, 12345);
+    out.sort_unstable();
+    assert_eq!(out, tids);
+  }
+
+  #[test]
+  fn shuffled_tids_is_deterministic_per_seed() {
+    let tids: Vec<usize> = (0..8).collect();
+    assert_eq!(shuffled_tids(&tids, 7), shuffled_tids(&tids, 7));
+  }
+
+  #[test]
+  fn visit_queue_push_pop_is_lifo() {
+    let q = VisitQueue::new();
+    q.push((1, 10));
+    q.push((2, 20));
+    assert_eq!(q.pop(), Some((2, 20)));
+    assert_eq!(q.pop(), Some((1, 10)));
+    assert_eq!(q.pop(), None);
+  }
+
+  #[test]
+  fn visit_queue_steal_takes_from_the_other_end() {
+    let q = VisitQueue::new();
+    q.push((1, 10));
+    q.push((2, 20));
+    // Stealing takes from the top (FIFO), the owning thread's pop takes
+    // from the bottom (LIFO) — opposite ends of the same deque.
+    assert_eq!(q.steal(), Some((1, 10)));
+    assert_eq!(q.pop(), Some((2, 20)));
+  }
+}
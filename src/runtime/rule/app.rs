@@ -30,6 +30,18 @@ pub fn apply(ctx: ReduceCtx) -> bool {
     return true;
   }
 
+  // (ε a)
+  // --------- APP-ERA
+  // ε
+  // (erase a, since nothing will ever apply it)
+  if arg0.tag() == Tag::ERA {
+    ctx.heap.inc_cost(ctx.tid);
+    era::erase(ctx.heap, ctx.prog, ctx.tid, ctx.heap.take_arg(ctx.term, 1));
+    ctx.heap.link(*ctx.host, Era());
+    ctx.heap.free(ctx.tid, ctx.term.loc(0), 2);
+    return true;
+  }
+
   // ({a b} c)
   // --------------- APP-SUP
   // dup x0 x1 = c
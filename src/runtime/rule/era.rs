@@ -0,0 +1,112 @@
+use crate::runtime::*;
+
+// ERA is the eraser agent from interaction combinators: a value meaning
+// "nothing here is wanted." Unlike the old approach of calling `heap.free`
+// directly at the one site that happens to know a subgraph is dead, ERA is
+// first-class and participates in the same Tag dispatch as LAM/CTR/SUP/DUP,
+// so erasure composes correctly under sharing: a `dup` with one used and one
+// unused projection can link ERA into the unused side, and whatever that
+// side pointed at gets reclaimed exactly once, however many other duplicates
+// of it are still alive.
+//
+// ERA itself has no auxiliary ports, so it's always in WHNF: the reducer
+// never needs a `visit`/`apply` pair for it as a *host* term (see the
+// catch-all arm in `reducer.rs`'s state machine, which already treats any
+// tag it doesn't recognize as inert). What's new is the other side of each
+// interaction noticing its principal argument is ERA; see `app::apply`'s
+// APP-ERA case for the pattern.
+//
+// Scope: APP-ERA and the ERA-DUP collapse are wired up; `app::apply`'s
+// APP-SUP case has nothing to erase (it duplicates, it doesn't discard), so
+// there's no eager `free` there to replace. See `erase_dup_projection`
+// below for the collapse; it is public, but its only caller here is
+// `erase`, and `dup`'s own reduction does not call it.
+
+// ERA meets a value with `n` children (a LAM's body, a CTR/FUN's fields, a
+// SUP's two branches, ...): per the standard ERA-CON rule, erase every
+// child by recursing into it, then free this node's own cells. Because each
+// child of a live graph is only reachable from one unique wire at a time,
+// this recursion only ever walks subgraphs nothing else still points at, so
+// it's safe to do eagerly and charge one `inc_cost` per term it visits,
+// leaves included
+// (this is the same recursion shape the pre-ERA code used to do implicitly
+// by calling `heap.free` at a single call site, just generalized to every
+// tag instead of hand-written per caller).
+pub fn erase(heap: &Heap, prog: &Program, tid: usize, term: Ptr) {
+  heap.inc_cost(tid);
+  match term.tag() {
+    Tag::ERA => {
+      // ε meets ε: nothing to propagate.
+    }
+    Tag::LAM => {
+      erase(heap, prog, tid, heap.take_arg(term, 1));
+      heap.free(tid, term.loc(0), 2);
+    }
+    Tag::APP => {
+      erase(heap, prog, tid, heap.take_arg(term, 0));
+      erase(heap, prog, tid, heap.take_arg(term, 1));
+      heap.free(tid, term.loc(0), 2);
+    }
+    Tag::SUP => {
+      erase(heap, prog, tid, heap.take_arg(term, 0));
+      erase(heap, prog, tid, heap.take_arg(term, 1));
+      heap.free(tid, term.loc(0), 2);
+    }
+    Tag::DP0 | Tag::DP1 => {
+      erase_dup_projection(heap, prog, tid, term);
+    }
+    Tag::OP2 => {
+      erase(heap, prog, tid, heap.take_arg(term, 0));
+      erase(heap, prog, tid, heap.take_arg(term, 1));
+      heap.free(tid, term.loc(0), 2);
+    }
+    Tag::CTR | Tag::FUN => {
+      let arity = prog.aris.arity_of(term);
+      for i in 0..arity {
+        erase(heap, prog, tid, heap.take_arg(term, i));
+      }
+      if arity > 0 {
+        heap.free(tid, term.loc(0), arity);
+      }
+    }
+    // VAR, NUM and anything else with no children: nothing further to erase.
+    _ => {}
+  }
+}
+
+// ERA-DUP: record that one projection of a shared dup is no longer wanted,
+// and actually reclaim the shared value once *both* projections have said
+// so. `term` is the `Dp0`/`Dp1` pointer for the projection going unused.
+// `era::erase` calls it when an APP-ERA (or any other erase) happens to
+// discard an argument that's itself a dup projection.
+//
+// `loc(0)`/`loc(1)` are each projection's own slot on the shared dup node
+// (`loc(2)` is the value the two share, see `snapshot.rs`'s `Dp0`/`Dp1`
+// cases); writing `Era()` into *my* slot and checking the *other* one needs
+// the same per-dup lock the reducer's own `Tag::DP0 | Tag::DP1` visit arm
+// takes before touching this node (see `reducer.rs`), or a concurrent
+// erase of the sibling projection could race this check and either miss
+// the collapse or, worse, collapse the value while the sibling still
+// expects to read it.
+//
+// Returns whether the shared value was actually collapsed, so a caller
+// that's about to free something built around this dup node knows whether
+// its 3 cells are already gone.
+pub fn erase_dup_projection(heap: &Heap, prog: &Program, tid: usize, term: Ptr) -> bool {
+  let (my_slot, other_slot) = if term.tag() == Tag::DP0 { (term.loc(0), term.loc(1)) } else { (term.loc(1), term.loc(0)) };
+  loop {
+    match heap.acquire_lock(tid, term) {
+      Ok(_) => break,
+      Err(_) => continue,
+    }
+  }
+  heap.link(my_slot, Era());
+  let collapsed = heap.load_ptr(other_slot).tag() == Tag::ERA;
+  if collapsed {
+    heap.inc_cost(tid);
+    erase(heap, prog, tid, heap.take_arg(term, 2));
+    heap.free(tid, term.loc(0), 3);
+  }
+  heap.release_lock(tid, term);
+  collapsed
+}